@@ -1,4 +1,6 @@
 use super::camera::*;
+use super::perturbation;
+use super::texture::Texture;
 use std::borrow::Cow;
 use wgpu::util::DeviceExt;
 
@@ -27,6 +29,16 @@ const VERTICES: &[Vertex] = &[
     },
 ];
 
+/// One-shot captures (`render_offscreen`, `render_headless`) start from a
+/// freshly zero-initialized `PingPongBuffer` with no prior frame to read,
+/// unlike the live `draw()` loop, which converges `buffer.wgsl`'s
+/// `mix(prev, color, 0.15)` feedback naturally over real frames. Run the
+/// buffer pass against itself this many times before the final blit so a
+/// one-shot render starts from (effectively) the converged steady state
+/// instead of 15% of it. `0.85^48 < 0.0005`, well under the rounding error
+/// of an 8-bit framebuffer.
+const FEEDBACK_CONVERGENCE_PASSES: u32 = 48;
+
 impl Vertex {
     fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
@@ -41,8 +53,41 @@ impl Vertex {
     }
 }
 
+/// ShaderToy-compatible globals (`iResolution`, `iTime`, ...), bound alongside
+/// `CameraUniform` so shaders ported from ShaderToy need minimal changes.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShaderToyUniform {
+    resolution: [f32; 3],
+    time: f32,
+    time_delta: f32,
+    frame: i32,
+    // std140 requires `mouse` (a vec4) to start on a 16-byte boundary.
+    _padding: [f32; 2],
+    mouse: [f32; 4],
+}
+
+impl ShaderToyUniform {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            resolution: [width as f32, height as f32, 1.0],
+            time: 0.0,
+            time_delta: 0.0,
+            frame: 0,
+            _padding: [0.0; 2],
+            mouse: [0.0; 4],
+        }
+    }
+}
+
+/// Events the app injects into the winit loop from outside of window events.
+pub enum UserEvent {
+    /// `src/shader.wgsl` or `src/buffer.wgsl` changed on disk.
+    ShaderChanged,
+}
+
 struct AppInner {
-    event_loop: Option<winit::event_loop::EventLoop<()>>,
+    event_loop: Option<winit::event_loop::EventLoop<UserEvent>>,
     window: winit::window::Window,
     instance: wgpu::Instance,
     device: wgpu::Device,
@@ -56,6 +101,101 @@ struct AppInner {
     sample_count: u32,
 }
 
+/// Two same-size offscreen render targets used for feedback effects: each
+/// frame one is read from (last frame's result) while the other is written
+/// to, then the roles swap. `bind_groups[i]` exposes `views[i]` as a sampled
+/// input, so the read bind group for a frame is `bind_groups[read_index()]`.
+struct PingPongBuffer {
+    textures: [wgpu::Texture; 2],
+    views: [wgpu::TextureView; 2],
+    bind_groups: [wgpu::BindGroup; 2],
+    write_is_a: bool,
+}
+
+impl PingPongBuffer {
+    fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let make_texture = || {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("ping-pong buffer"),
+                size: wgpu::Extent3d {
+                    width: config.width,
+                    height: config.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            })
+        };
+        let textures = [make_texture(), make_texture()];
+        let views = [
+            textures[0].create_view(&wgpu::TextureViewDescriptor::default()),
+            textures[1].create_view(&wgpu::TextureViewDescriptor::default()),
+        ];
+        let make_bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+                label: Some("feedback_bind_group"),
+            })
+        };
+        let bind_groups = [make_bind_group(&views[0]), make_bind_group(&views[1])];
+
+        Self {
+            textures,
+            views,
+            bind_groups,
+            write_is_a: true,
+        }
+    }
+
+    fn read_index(&self) -> usize {
+        if self.write_is_a {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn write_index(&self) -> usize {
+        if self.write_is_a {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn swap(&mut self) {
+        self.write_is_a = !self.write_is_a;
+    }
+}
+
 pub struct App {
     inner: AppInner,
     shader: wgpu::ShaderModule,
@@ -64,6 +204,28 @@ pub struct App {
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
     camera_controller: CameraController,
+    shader_toy: ShaderToyUniform,
+    shader_toy_buffer: wgpu::Buffer,
+    shader_toy_bind_group: wgpu::BindGroup,
+    // Kept alive so the views/samplers bound in `channels_bind_group` stay valid.
+    channels: [Texture; 4],
+    channels_bind_group: wgpu::BindGroup,
+    feedback_bind_group_layout: wgpu::BindGroupLayout,
+    buffer_shader: wgpu::ShaderModule,
+    buffer_pipeline: wgpu::RenderPipeline,
+    ping_pong: PingPongBuffer,
+    orbit_buffer: wgpu::Buffer,
+    orbit2_buffer: wgpu::Buffer,
+    orbit_bind_group: wgpu::BindGroup,
+    // Kept alive for the lifetime of the app; dropping it stops the watch.
+    #[cfg(not(target_arch = "wasm32"))]
+    _shader_watcher: notify::RecommendedWatcher,
+    start_time: std::time::Instant,
+    last_draw: std::time::Instant,
+    animate: bool,
+    is_dragging: bool,
+    mouse_pos: [f32; 2],
+    click_origin: [f32; 2],
 }
 
 impl App {
@@ -90,7 +252,9 @@ impl App {
 
     async fn new_common() -> Self {
         let sample_count = 4;
-        let event_loop = winit::event_loop::EventLoop::new();
+        let event_loop = winit::event_loop::EventLoopBuilder::<UserEvent>::with_user_event().build();
+        #[cfg(not(target_arch = "wasm32"))]
+        let shader_watcher = start_shader_watcher(event_loop.create_proxy());
         let window = winit::window::Window::new(&event_loop).unwrap();
 
         let size = window.inner_size();
@@ -117,10 +281,12 @@ impl App {
                 &wgpu::DeviceDescriptor {
                     label: None,
                     features: wgpu::Features::empty(),
-                    // Make sure we use the texture resolution limits from the adapte,
-                    // so we can support images the size of the swapchain.
-                    limits: wgpu::Limits::downlevel_webgl2_defaults()
-                        .using_resolution(adapter.limits()),
+                    // The WebGL2-class downlevel defaults cap `max_bind_groups` at 4
+                    // (camera + shader_toy + channels + feedback already uses all of
+                    // them) and forbid fragment-stage storage buffers entirely, which
+                    // the perturbation orbit's group 4 needs. Request the adapter's
+                    // native limits instead.
+                    limits: adapter.limits(),
                 },
                 None,
             )
@@ -130,54 +296,8 @@ impl App {
 
         let swapchain_format = surface.get_supported_formats(&adapter)[0];
 
-        let camera = CameraUniform::new(size.width as f32 / size.height as f32);
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[camera]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-        let camera_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-                label: Some("camera_bind_group_layout"),
-            });
-        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: Some("camera_bind_group"),
-        });
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&camera_bind_group_layout],
-                push_constant_ranges: &[],
-            });
-
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shader.wgsl"))),
-        });
-
-        let render_pipeline = generate_render_pipeline(
-            &shader,
-            swapchain_format,
-            &render_pipeline_layout,
-            sample_count,
-            |render_pipeline_descriptor| device.create_render_pipeline(&render_pipeline_descriptor),
-        );
+        let resources =
+            build_render_resources(&device, &queue, size.width, size.height, swapchain_format, sample_count);
 
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -189,36 +309,51 @@ impl App {
 
         surface.configure(&device, &config);
 
-        let multisampled_framebuffer =
-            create_multisampled_framebuffer(&device, &config, sample_count);
-
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(VERTICES),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
         Self {
             inner: AppInner {
                 window,
                 event_loop: Some(event_loop),
                 instance,
-                render_pipeline,
+                render_pipeline: resources.render_pipeline,
                 device,
                 queue,
                 config,
                 surface,
                 swapchain_format,
-                render_pipeline_layout,
-                multisampled_framebuffer,
+                render_pipeline_layout: resources.render_pipeline_layout,
+                multisampled_framebuffer: resources.multisampled_framebuffer,
                 sample_count,
             },
-            shader,
-            vertex_buffer,
-            camera,
-            camera_buffer,
-            camera_bind_group,
-            camera_controller: CameraController::new(),
+            shader: resources.shader,
+            vertex_buffer: resources.vertex_buffer,
+            camera: resources.camera,
+            camera_buffer: resources.camera_buffer,
+            camera_bind_group: resources.camera_bind_group,
+            camera_controller: {
+                let mut c = CameraController::new();
+                c.update_window_size(size.width as f32, size.height as f32);
+                c
+            },
+            shader_toy: resources.shader_toy,
+            shader_toy_buffer: resources.shader_toy_buffer,
+            shader_toy_bind_group: resources.shader_toy_bind_group,
+            channels: resources.channels,
+            channels_bind_group: resources.channels_bind_group,
+            feedback_bind_group_layout: resources.feedback_bind_group_layout,
+            buffer_shader: resources.buffer_shader,
+            buffer_pipeline: resources.buffer_pipeline,
+            ping_pong: resources.ping_pong,
+            orbit_buffer: resources.orbit_buffer,
+            orbit2_buffer: resources.orbit2_buffer,
+            orbit_bind_group: resources.orbit_bind_group,
+            #[cfg(not(target_arch = "wasm32"))]
+            _shader_watcher: shader_watcher,
+            start_time: std::time::Instant::now(),
+            last_draw: std::time::Instant::now(),
+            animate: false,
+            is_dragging: false,
+            mouse_pos: [0.0, 0.0],
+            click_origin: [0.0, 0.0],
         }
     }
 
@@ -231,7 +366,12 @@ impl App {
             use winit::event::KeyboardInput;
             use winit::event::VirtualKeyCode;
             use winit::event::WindowEvent;
-            control_flow.set_wait(); // Bc Mandelbrot isn't animated
+            control_flow.set_wait();
+
+            if self.animate {
+                control_flow.set_poll();
+                self.inner.window.request_redraw();
+            }
 
             let dt = last.elapsed();
             //println!("Elapsed since last frame: {}ns", dt.as_nanos());
@@ -239,6 +379,42 @@ impl App {
 
             if self.camera_controller.update_camera(dt, &mut self.camera) {
                 self.inner.window.request_redraw();
+                if self.camera_controller.take_center_dirty() {
+                    let center = self.camera_controller.center();
+                    let mut orbit = [[0.0f32; 2]; perturbation::MAX_ORBIT_ITER];
+                    let orbit_len = perturbation::reference_orbit(center, &mut orbit);
+                    self.camera.set_orbit_len(orbit_len);
+                    self.inner
+                        .queue
+                        .write_buffer(&self.orbit_buffer, 0, bytemuck::cast_slice(&orbit));
+
+                    // Re-seat the secondary (glitch fallback) orbit towards a
+                    // viewport corner too. It's only recomputed alongside the
+                    // primary when the center moves (a pan or a scroll-wheel
+                    // zoom), not on a keyboard zoom, so it can go stale
+                    // relative to the current zoom level in between; that's
+                    // an acceptable simplification since it's a fallback path,
+                    // not the primary render.
+                    let half_extent = 0.5 * self.camera.zoom() as f64;
+                    let secondary_center = (
+                        center.0 + half_extent * self.camera.aspect_ratio() as f64,
+                        center.1 + half_extent,
+                    );
+                    let mut orbit2 = [[0.0f32; 2]; perturbation::MAX_ORBIT_ITER];
+                    let orbit2_len = perturbation::reference_orbit(secondary_center, &mut orbit2);
+                    self.camera.set_orbit2(
+                        orbit2_len,
+                        [
+                            (secondary_center.0 - center.0) as f32,
+                            (secondary_center.1 - center.1) as f32,
+                        ],
+                    );
+                    self.inner.queue.write_buffer(
+                        &self.orbit2_buffer,
+                        0,
+                        bytemuck::cast_slice(&orbit2),
+                    );
+                }
                 self.inner.queue.write_buffer(
                     &self.camera_buffer,
                     0,
@@ -261,6 +437,8 @@ impl App {
                     self.inner.config.height = size.height;
                     self.camera_controller
                         .update_aspect_ratio(size.width as f32 / size.height as f32);
+                    self.camera_controller
+                        .update_window_size(size.width as f32, size.height as f32);
                     self.inner
                         .surface
                         .configure(&self.inner.device, &self.inner.config);
@@ -269,6 +447,13 @@ impl App {
                         &self.inner.config,
                         self.inner.sample_count,
                     );
+                    self.ping_pong = PingPongBuffer::new(
+                        &self.inner.device,
+                        &self.inner.config,
+                        self.inner.swapchain_format,
+                        &self.feedback_bind_group_layout,
+                    );
+                    self.shader_toy.resolution = [size.width as f32, size.height as f32, 1.0];
                     self.inner.window.request_redraw();
                 }
                 Event::RedrawRequested(_) => {
@@ -295,6 +480,79 @@ impl App {
                     self.reload_shader();
                     self.inner.window.request_redraw();
                 }
+                Event::WindowEvent {
+                    event:
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    state: winit::event::ElementState::Pressed,
+                                    virtual_keycode: Some(VirtualKeyCode::P),
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                } => {
+                    self.save_screenshot(
+                        self.inner.config.width,
+                        self.inner.config.height,
+                        std::path::Path::new("screenshot.png"),
+                    );
+                }
+                Event::UserEvent(UserEvent::ShaderChanged) => {
+                    log::info!("Shader file changed on disk, reloading");
+                    self.reload_shader();
+                    self.inner.window.request_redraw();
+                }
+                Event::WindowEvent {
+                    event:
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    state: winit::event::ElementState::Pressed,
+                                    virtual_keycode: Some(VirtualKeyCode::Space),
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                } => {
+                    self.animate = !self.animate;
+                    log::info!("Animation: {}", self.animate);
+                    self.inner.window.request_redraw();
+                }
+                Event::WindowEvent {
+                    event: ref we @ WindowEvent::CursorMoved { position, .. },
+                    ..
+                } => {
+                    self.mouse_pos = [position.x as f32, position.y as f32];
+                    if self.is_dragging {
+                        self.shader_toy.mouse = [
+                            self.mouse_pos[0],
+                            self.mouse_pos[1],
+                            self.click_origin[0],
+                            self.click_origin[1],
+                        ];
+                        self.inner.window.request_redraw();
+                    }
+                    self.camera_controller.process_events(we);
+                }
+                Event::WindowEvent {
+                    event:
+                        ref we
+                        @ WindowEvent::MouseInput {
+                            state,
+                            button: winit::event::MouseButton::Left,
+                            ..
+                        },
+                    ..
+                } => {
+                    self.is_dragging = state == winit::event::ElementState::Pressed;
+                    if self.is_dragging {
+                        self.click_origin = self.mouse_pos;
+                    }
+                    self.camera_controller.process_events(we);
+                }
                 Event::WindowEvent { event, .. } => {
                     self.camera_controller.process_events(&event);
                 }
@@ -303,7 +561,18 @@ impl App {
         });
     }
 
-    fn draw(&self) {
+    fn draw(&mut self) {
+        let now = std::time::Instant::now();
+        self.shader_toy.time = (now - self.start_time).as_secs_f32();
+        self.shader_toy.time_delta = (now - self.last_draw).as_secs_f32();
+        self.shader_toy.frame = self.shader_toy.frame.wrapping_add(1);
+        self.last_draw = now;
+        self.inner.queue.write_buffer(
+            &self.shader_toy_buffer,
+            0,
+            bytemuck::cast_slice(&[self.shader_toy]),
+        );
+
         let frame = self
             .inner
             .surface
@@ -316,6 +585,35 @@ impl App {
             .inner
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut buffer_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Buffer A"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.ping_pong.views[self.ping_pong.write_index()],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            buffer_pass.set_pipeline(&self.buffer_pipeline);
+            buffer_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            buffer_pass.set_bind_group(1, &self.shader_toy_bind_group, &[]);
+            buffer_pass.set_bind_group(2, &self.channels_bind_group, &[]);
+            buffer_pass.set_bind_group(
+                3,
+                &self.ping_pong.bind_groups[self.ping_pong.read_index()],
+                &[],
+            );
+            buffer_pass.set_bind_group(4, &self.orbit_bind_group, &[]);
+            buffer_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            buffer_pass.draw(0..(VERTICES.len() as u32), 0..1);
+        }
+        self.ping_pong.swap();
+
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Main"),
@@ -332,6 +630,14 @@ impl App {
 
             rpass.set_pipeline(&self.inner.render_pipeline);
             rpass.set_bind_group(0, &self.camera_bind_group, &[]);
+            rpass.set_bind_group(1, &self.shader_toy_bind_group, &[]);
+            rpass.set_bind_group(2, &self.channels_bind_group, &[]);
+            rpass.set_bind_group(
+                3,
+                &self.ping_pong.bind_groups[self.ping_pong.read_index()],
+                &[],
+            );
+            rpass.set_bind_group(4, &self.orbit_bind_group, &[]);
             rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
             rpass.draw(0..(VERTICES.len() as u32), 0..1);
         }
@@ -340,35 +646,586 @@ impl App {
         frame.present();
     }
 
+    /// Reloads `shader.wgsl` and `buffer.wgsl` independently, validating each
+    /// through naga before swapping its pipeline. A shader that fails to
+    /// parse or validate is logged and left as-is, keeping the last-good
+    /// pipeline running instead of a broken one.
     fn reload_shader(&mut self) {
+        match self.try_load_source("src/shader.wgsl") {
+            Ok(data) => {
+                let shader = self.inner.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(Cow::Owned(data)),
+                });
+                generate_render_pipeline(
+                    &shader,
+                    self.inner.swapchain_format,
+                    &self.inner.render_pipeline_layout,
+                    self.inner.sample_count,
+                    |render_pipeline_descriptor| {
+                        let render_pipeline = self
+                            .inner
+                            .device
+                            .create_render_pipeline(&render_pipeline_descriptor);
+                        self.inner.render_pipeline = render_pipeline;
+                    },
+                );
+                self.shader = shader;
+            }
+            Err(e) => log::error!("src/shader.wgsl: keeping last-good pipeline:\n{}", e),
+        }
+
+        match self.try_load_source("src/buffer.wgsl") {
+            Ok(data) => {
+                let buffer_shader =
+                    self.inner
+                        .device
+                        .create_shader_module(wgpu::ShaderModuleDescriptor {
+                            label: Some("buffer_a"),
+                            source: wgpu::ShaderSource::Wgsl(Cow::Owned(data)),
+                        });
+                generate_render_pipeline(
+                    &buffer_shader,
+                    self.inner.swapchain_format,
+                    &self.inner.render_pipeline_layout,
+                    1,
+                    |render_pipeline_descriptor| {
+                        let buffer_pipeline = self
+                            .inner
+                            .device
+                            .create_render_pipeline(&render_pipeline_descriptor);
+                        self.buffer_pipeline = buffer_pipeline;
+                    },
+                );
+                self.buffer_shader = buffer_shader;
+            }
+            Err(e) => log::error!("src/buffer.wgsl: keeping last-good pipeline:\n{}", e),
+        }
+    }
+
+    /// Reads `path` and validates it as WGSL, returning the source on
+    /// success so the caller can hand it to `wgpu` with confidence it won't
+    /// trip `catch_all_error`.
+    fn try_load_source(&self, path: &str) -> Result<String, String> {
         use std::io::Read;
         let mut data = String::new();
-        std::fs::File::open("src/shader.wgsl")
-            .expect("Can't open `shader.wgsl`")
+        std::fs::File::open(path)
+            .map_err(|e| format!("Can't open `{}`: {}", path, e))?
             .read_to_string(&mut data)
-            .unwrap();
+            .map_err(|e| format!("Can't read `{}`: {}", path, e))?;
+        validate_wgsl(&data)?;
+        Ok(data)
+    }
 
-        let shader = self
-            .inner
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: None,
-                source: wgpu::ShaderSource::Wgsl(Cow::Owned(data)),
-            });
+    /// Renders the current view into an offscreen texture at an arbitrary
+    /// `width` x `height`, independent of the window's swapchain, and reads
+    /// it back into an RGBA image. The live `camera_buffer`/`ping_pong` are
+    /// left untouched once this returns, aside from a transient aspect-ratio
+    /// override needed to match the requested resolution.
+    fn render_offscreen(&mut self, width: u32, height: u32) -> image::RgbaImage {
+        let device = &self.inner.device;
+        let queue = &self.inner.queue;
 
-        generate_render_pipeline(
-            &shader,
-            self.inner.swapchain_format,
-            &self.inner.render_pipeline_layout,
-            self.inner.sample_count,
-            |render_pipeline_descriptor| {
-                let render_pipeline = self
-                    .inner
-                    .device
-                    .create_render_pipeline(&render_pipeline_descriptor);
-                self.inner.render_pipeline = render_pipeline;
+        let render_camera = self.camera.with_aspect_ratio(width as f32 / height as f32);
+        queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[render_camera]),
+        );
+
+        let format = self.inner.swapchain_format;
+        let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("screenshot color target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
             },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let offscreen_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::AutoNoVsync,
+        };
+        let multisampled_framebuffer =
+            create_multisampled_framebuffer(device, &offscreen_config, self.inner.sample_count);
+        let mut ping_pong = PingPongBuffer::new(
+            device,
+            &offscreen_config,
+            format,
+            &self.feedback_bind_group_layout,
         );
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("screenshot encoder"),
+        });
+
+        converge_feedback_buffer(
+            &mut encoder,
+            &self.buffer_pipeline,
+            &self.camera_bind_group,
+            &self.shader_toy_bind_group,
+            &self.channels_bind_group,
+            &self.orbit_bind_group,
+            &mut ping_pong,
+            &self.vertex_buffer,
+        );
+
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Screenshot Main"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &multisampled_framebuffer,
+                    resolve_target: Some(&color_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            rpass.set_pipeline(&self.inner.render_pipeline);
+            rpass.set_bind_group(0, &self.camera_bind_group, &[]);
+            rpass.set_bind_group(1, &self.shader_toy_bind_group, &[]);
+            rpass.set_bind_group(2, &self.channels_bind_group, &[]);
+            rpass.set_bind_group(3, &ping_pong.bind_groups[ping_pong.write_index()], &[]);
+            rpass.set_bind_group(4, &self.orbit_bind_group, &[]);
+            rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            rpass.draw(0..(VERTICES.len() as u32), 0..1);
+        }
+
+        let image = copy_texture_to_image(device, queue, encoder, &color_texture, width, height, format);
+        // Restore the window's aspect ratio now that the offscreen pass has
+        // submitted; the live camera buffer is back in sync before `draw()`
+        // next writes it.
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera]));
+        image
+    }
+
+    /// Renders the current view at `width` x `height` and writes it to `path` as a PNG.
+    pub fn save_screenshot(&mut self, width: u32, height: u32, path: &std::path::Path) {
+        log::info!(
+            "Rendering {}x{} screenshot to {}",
+            width,
+            height,
+            path.display()
+        );
+        self.render_offscreen(width, height)
+            .save(path)
+            .expect("Failed to write screenshot");
+    }
+}
+
+/// Submits `encoder` (which must already contain the draw commands that
+/// wrote into `color_texture`), copies `color_texture` back to a row-padded
+/// `MAP_READ` buffer (rows must be padded to `COPY_BYTES_PER_ROW_ALIGNMENT`),
+/// and decodes it into an RGBA image once the GPU is done. Shared by
+/// `App::render_offscreen` and the headless `--render` CLI path, which have
+/// no other state in common.
+fn copy_texture_to_image(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    mut encoder: wgpu::CommandEncoder,
+    color_texture: &wgpu::Texture,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> image::RgbaImage {
+    let bytes_per_pixel = 4;
+    let unpadded_bytes_per_row = width * bytes_per_pixel;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+    let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("screenshot readback buffer"),
+        size: (padded_bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: color_texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &output_buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: std::num::NonZeroU32::new(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    queue.submit(Some(encoder.finish()));
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let buffer_slice = output_buffer.slice(..);
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    rx.recv()
+        .expect("screenshot map_async callback dropped")
+        .expect("Failed to map screenshot buffer");
+
+    let mapped = buffer_slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    for row in mapped.chunks(padded_bytes_per_row as usize) {
+        pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+    }
+    drop(mapped);
+    output_buffer.unmap();
+
+    if format == wgpu::TextureFormat::Bgra8Unorm || format == wgpu::TextureFormat::Bgra8UnormSrgb {
+        for pixel in pixels.chunks_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    image::RgbaImage::from_raw(width, height, pixels).expect("screenshot pixel buffer size mismatch")
+}
+
+/// Parses and validates WGSL source through naga's WGSL front-end, the same
+/// checks `wgpu` runs internally, so compile errors surface with source spans
+/// before we ever hand the source to `create_shader_module`.
+fn validate_wgsl(source: &str) -> Result<(), String> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| e.emit_to_string(source))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| e.emit_to_string(source))?;
+    Ok(())
+}
+
+/// Everything `new_common` (windowed) and `render_headless` (headless CLI)
+/// need identically: uniforms, bind groups, pipelines, and the feedback
+/// ping-pong buffer, sized to `width`x`height`. Stops short of
+/// `Window`/`Surface` creation, which only the windowed path needs, and of
+/// the `wgpu::SurfaceConfiguration` itself, since only the windowed path
+/// uses it to configure a surface.
+struct RenderResources {
+    camera: CameraUniform,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    shader_toy: ShaderToyUniform,
+    shader_toy_buffer: wgpu::Buffer,
+    shader_toy_bind_group: wgpu::BindGroup,
+    channels: [Texture; 4],
+    channels_bind_group: wgpu::BindGroup,
+    feedback_bind_group_layout: wgpu::BindGroupLayout,
+    orbit_buffer: wgpu::Buffer,
+    orbit2_buffer: wgpu::Buffer,
+    orbit_bind_group: wgpu::BindGroup,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    shader: wgpu::ShaderModule,
+    buffer_shader: wgpu::ShaderModule,
+    render_pipeline: wgpu::RenderPipeline,
+    buffer_pipeline: wgpu::RenderPipeline,
+    multisampled_framebuffer: wgpu::TextureView,
+    ping_pong: PingPongBuffer,
+    vertex_buffer: wgpu::Buffer,
+}
+
+fn build_render_resources(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    width: u32,
+    height: u32,
+    swapchain_format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> RenderResources {
+    let mut orbit = [[0.0f32; 2]; perturbation::MAX_ORBIT_ITER];
+    let orbit_len = perturbation::reference_orbit((0.0, 0.0), &mut orbit);
+
+    // A second reference orbit, offset towards a viewport corner, for
+    // `buffer.wgsl` to fall back to when a pixel glitches against `orbit`
+    // (see the `CameraUniform` doc comment).
+    let aspect_ratio = width as f32 / height as f32;
+    let secondary_center = (0.5 * aspect_ratio as f64, 0.5);
+    let mut orbit2 = [[0.0f32; 2]; perturbation::MAX_ORBIT_ITER];
+    let orbit2_len = perturbation::reference_orbit(secondary_center, &mut orbit2);
+
+    let mut camera = CameraUniform::new(aspect_ratio, orbit_len);
+    camera.set_orbit2(
+        orbit2_len,
+        [secondary_center.0 as f32, secondary_center.1 as f32],
+    );
+    let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Camera Buffer"),
+        contents: bytemuck::cast_slice(&[camera]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let camera_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("camera_bind_group_layout"),
+        });
+    let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &camera_bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: camera_buffer.as_entire_binding(),
+        }],
+        label: Some("camera_bind_group"),
+    });
+
+    let shader_toy = ShaderToyUniform::new(width, height);
+    let shader_toy_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("ShaderToy Buffer"),
+        contents: bytemuck::cast_slice(&[shader_toy]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let shader_toy_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("shader_toy_bind_group_layout"),
+        });
+    let shader_toy_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &shader_toy_bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: shader_toy_buffer.as_entire_binding(),
+        }],
+        label: Some("shader_toy_bind_group"),
+    });
+
+    let channel_paths = parse_channel_args();
+    let channels = channel_paths.map(|path| match path {
+        Some(path) => Texture::from_path(device, queue, &path),
+        None => Texture::placeholder(device, queue),
+    });
+    let channels_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &channel_bind_group_layout_entries(),
+            label: Some("channels_bind_group_layout"),
+        });
+    let channels_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &channels_bind_group_layout,
+        entries: &channel_bind_group_entries(&channels),
+        label: Some("channels_bind_group"),
+    });
+
+    let feedback_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("feedback_bind_group_layout"),
+        });
+
+    let orbit_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Orbit Buffer"),
+        contents: bytemuck::cast_slice(&orbit),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+    });
+    let orbit2_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Secondary Orbit Buffer"),
+        contents: bytemuck::cast_slice(&orbit2),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+    });
+    let orbit_storage_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    };
+    let orbit_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[orbit_storage_entry(0), orbit_storage_entry(1)],
+            label: Some("orbit_bind_group_layout"),
+        });
+    let orbit_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: &orbit_bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: orbit_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: orbit2_buffer.as_entire_binding(),
+            },
+        ],
+        label: Some("orbit_bind_group"),
+    });
+
+    let render_pipeline_layout =
+        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &shader_toy_bind_group_layout,
+                &channels_bind_group_layout,
+                &feedback_bind_group_layout,
+                &orbit_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shader.wgsl"))),
+    });
+    let buffer_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("buffer_a"),
+        source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("buffer.wgsl"))),
+    });
+
+    let render_pipeline = generate_render_pipeline(
+        &shader,
+        swapchain_format,
+        &render_pipeline_layout,
+        sample_count,
+        |render_pipeline_descriptor| device.create_render_pipeline(&render_pipeline_descriptor),
+    );
+    let buffer_pipeline = generate_render_pipeline(
+        &buffer_shader,
+        swapchain_format,
+        &render_pipeline_layout,
+        1,
+        |render_pipeline_descriptor| device.create_render_pipeline(&render_pipeline_descriptor),
+    );
+
+    let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format: swapchain_format,
+        width,
+        height,
+        present_mode: wgpu::PresentMode::AutoNoVsync,
+    };
+    let multisampled_framebuffer =
+        create_multisampled_framebuffer(device, &config, sample_count);
+    let ping_pong =
+        PingPongBuffer::new(device, &config, swapchain_format, &feedback_bind_group_layout);
+
+    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Vertex Buffer"),
+        contents: bytemuck::cast_slice(VERTICES),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    RenderResources {
+        camera,
+        camera_buffer,
+        camera_bind_group,
+        shader_toy,
+        shader_toy_buffer,
+        shader_toy_bind_group,
+        channels,
+        channels_bind_group,
+        feedback_bind_group_layout,
+        orbit_buffer,
+        orbit2_buffer,
+        orbit_bind_group,
+        render_pipeline_layout,
+        shader,
+        buffer_shader,
+        render_pipeline,
+        buffer_pipeline,
+        multisampled_framebuffer,
+        ping_pong,
+        vertex_buffer,
+    }
+}
+
+/// Runs `buffer.wgsl`'s feedback pass against itself `FEEDBACK_CONVERGENCE_PASSES`
+/// times, leaving the converged result in `ping_pong`'s current write slot
+/// (no trailing swap) so the caller's subsequent main pass can read it via
+/// `ping_pong.bind_groups[ping_pong.write_index()]`, matching the one-shot
+/// capture paths' existing convention.
+#[allow(clippy::too_many_arguments)]
+fn converge_feedback_buffer(
+    encoder: &mut wgpu::CommandEncoder,
+    buffer_pipeline: &wgpu::RenderPipeline,
+    camera_bind_group: &wgpu::BindGroup,
+    shader_toy_bind_group: &wgpu::BindGroup,
+    channels_bind_group: &wgpu::BindGroup,
+    orbit_bind_group: &wgpu::BindGroup,
+    ping_pong: &mut PingPongBuffer,
+    vertex_buffer: &wgpu::Buffer,
+) {
+    for pass in 0..FEEDBACK_CONVERGENCE_PASSES {
+        {
+            let mut buffer_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Feedback Convergence Buffer A"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &ping_pong.views[ping_pong.write_index()],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            buffer_pass.set_pipeline(buffer_pipeline);
+            buffer_pass.set_bind_group(0, camera_bind_group, &[]);
+            buffer_pass.set_bind_group(1, shader_toy_bind_group, &[]);
+            buffer_pass.set_bind_group(2, channels_bind_group, &[]);
+            buffer_pass.set_bind_group(3, &ping_pong.bind_groups[ping_pong.read_index()], &[]);
+            buffer_pass.set_bind_group(4, orbit_bind_group, &[]);
+            buffer_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            buffer_pass.draw(0..(VERTICES.len() as u32), 0..1);
+        }
+        if pass + 1 != FEEDBACK_CONVERGENCE_PASSES {
+            ping_pong.swap();
+        }
     }
 }
 
@@ -433,3 +1290,252 @@ fn generate_render_pipeline<'a, T, F: FnOnce(wgpu::RenderPipelineDescriptor) ->
 fn catch_all_error(err: wgpu::Error) {
     log::error!("{}", err);
 }
+
+/// Watches `src/` (non-recursively) for writes to `shader.wgsl` or
+/// `buffer.wgsl`, posting `UserEvent::ShaderChanged` into the event loop so
+/// `run()` can reload without waiting on the `R` keybinding.
+///
+/// We watch the containing directory rather than the files directly: vim
+/// (and anything else that saves by writing a temp file and renaming it
+/// over the original) replaces the file's inode on save, which invalidates
+/// a file-specific inotify watch after the very first edit on Linux.
+#[cfg(not(target_arch = "wasm32"))]
+fn start_shader_watcher(
+    proxy: winit::event_loop::EventLoopProxy<UserEvent>,
+) -> notify::RecommendedWatcher {
+    use notify::Watcher;
+    let watched_files = ["shader.wgsl", "buffer.wgsl"];
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        match res {
+            Ok(event)
+                if event.kind.is_modify()
+                    && event.paths.iter().any(|p| {
+                        p.file_name()
+                            .and_then(|n| n.to_str())
+                            .map_or(false, |n| watched_files.contains(&n))
+                    }) =>
+            {
+                let _ = proxy.send_event(UserEvent::ShaderChanged);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("shader watcher error: {}", e),
+        }
+    })
+    .expect("Failed to create shader file watcher");
+    watcher
+        .watch(
+            std::path::Path::new("src"),
+            notify::RecursiveMode::NonRecursive,
+        )
+        .expect("Failed to watch `src`");
+    watcher
+}
+
+/// Reads `--channel0 <path>` .. `--channel3 <path>` from argv. Unset channels
+/// are bound to a 1x1 placeholder texture so the pipeline layout is static.
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_channel_args() -> [Option<std::path::PathBuf>; 4] {
+    let mut channels: [Option<std::path::PathBuf>; 4] = Default::default();
+    let args: Vec<String> = std::env::args().collect();
+    for (flag, slot) in [
+        ("--channel0", 0),
+        ("--channel1", 1),
+        ("--channel2", 2),
+        ("--channel3", 3),
+    ] {
+        if let Some(pos) = args.iter().position(|a| a == flag) {
+            if let Some(path) = args.get(pos + 1) {
+                channels[slot] = Some(std::path::PathBuf::from(path));
+            }
+        }
+    }
+    channels
+}
+
+#[cfg(target_arch = "wasm32")]
+fn parse_channel_args() -> [Option<std::path::PathBuf>; 4] {
+    Default::default()
+}
+
+/// Parses `--render WxH --out file.png` off the command line, for the
+/// headless screenshot mode. Returns `None` if either flag is absent, in
+/// which case `main` falls back to the normal windowed event loop.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_render_args() -> Option<(u32, u32, std::path::PathBuf)> {
+    let args: Vec<String> = std::env::args().collect();
+    let dims = args.get(args.iter().position(|a| a == "--render")? + 1)?;
+    let (w, h) = dims.split_once('x').or_else(|| dims.split_once('X'))?;
+    let width: u32 = w.parse().ok()?;
+    let height: u32 = h.parse().ok()?;
+    let out = args.get(args.iter().position(|a| a == "--out")? + 1)?;
+    Some((width, height, std::path::PathBuf::from(out)))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn parse_render_args() -> Option<(u32, u32, std::path::PathBuf)> {
+    None
+}
+
+/// Renders a single `width` x `height` frame and writes it to `path` as a
+/// PNG, without ever creating a `winit` window or `wgpu::Surface` — unlike
+/// `App::save_screenshot`, this is safe to call on a machine with no display
+/// server, which is the whole point of `--render`/`--out`.
+pub fn render_headless_block(width: u32, height: u32, path: &std::path::Path) {
+    pollster::block_on(render_headless(width, height, path));
+}
+
+async fn render_headless(width: u32, height: u32, path: &std::path::Path) {
+    let sample_count = 4;
+    let instance = wgpu::Instance::new(wgpu::Backends::all());
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        })
+        .await
+        .expect("Failed to find an appropriate adapter");
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: None,
+                features: wgpu::Features::empty(),
+                limits: adapter.limits(),
+            },
+            None,
+        )
+        .await
+        .expect("Failed to create device");
+    device.on_uncaptured_error(catch_all_error);
+
+    let swapchain_format = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    let mut resources =
+        build_render_resources(&device, &queue, width, height, swapchain_format, sample_count);
+
+    let color_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("headless render color target"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: swapchain_format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    });
+    let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("headless render encoder"),
+    });
+
+    converge_feedback_buffer(
+        &mut encoder,
+        &resources.buffer_pipeline,
+        &resources.camera_bind_group,
+        &resources.shader_toy_bind_group,
+        &resources.channels_bind_group,
+        &resources.orbit_bind_group,
+        &mut resources.ping_pong,
+        &resources.vertex_buffer,
+    );
+
+    {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Headless Main"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &resources.multisampled_framebuffer,
+                resolve_target: Some(&color_view),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::GREEN),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&resources.render_pipeline);
+        rpass.set_bind_group(0, &resources.camera_bind_group, &[]);
+        rpass.set_bind_group(1, &resources.shader_toy_bind_group, &[]);
+        rpass.set_bind_group(2, &resources.channels_bind_group, &[]);
+        rpass.set_bind_group(
+            3,
+            &resources.ping_pong.bind_groups[resources.ping_pong.write_index()],
+            &[],
+        );
+        rpass.set_bind_group(4, &resources.orbit_bind_group, &[]);
+        rpass.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+        rpass.draw(0..(VERTICES.len() as u32), 0..1);
+    }
+
+    let image = copy_texture_to_image(
+        &device,
+        &queue,
+        encoder,
+        &color_texture,
+        width,
+        height,
+        swapchain_format,
+    );
+    log::info!(
+        "Rendering {}x{} screenshot to {}",
+        width,
+        height,
+        path.display()
+    );
+    image.save(path).expect("Failed to write screenshot");
+}
+
+/// Always describes 4 texture+sampler slots, regardless of how many
+/// `--channelN` paths were actually passed (unset slots are bound to a 1x1
+/// placeholder in `new_common`/`render_headless` — see `parse_channel_args`).
+/// A layout sized to the present channels would need `reload_shader` to
+/// rebuild `channels_bind_group_layout` and `render_pipeline_layout`
+/// (and every other bind group alongside it, since `wgpu` pipeline layouts
+/// are all-or-nothing) on every hot reload, just to track a count that's
+/// fixed for the process's lifetime. Keeping it static is a conscious
+/// tradeoff, not an oversight.
+fn channel_bind_group_layout_entries() -> [wgpu::BindGroupLayoutEntry; 8] {
+    let mut entries = [wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }; 8];
+    for (channel, entry) in entries.chunks_mut(2).enumerate() {
+        entry[0].binding = channel as u32 * 2;
+        entry[1] = wgpu::BindGroupLayoutEntry {
+            binding: channel as u32 * 2 + 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+    }
+    entries
+}
+
+fn channel_bind_group_entries(channels: &[Texture; 4]) -> Vec<wgpu::BindGroupEntry> {
+    channels
+        .iter()
+        .enumerate()
+        .flat_map(|(channel, texture)| {
+            [
+                wgpu::BindGroupEntry {
+                    binding: channel as u32 * 2,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: channel as u32 * 2 + 1,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+            ]
+        })
+        .collect()
+}
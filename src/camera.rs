@@ -1,17 +1,62 @@
+/// The per-pixel offset from the reference orbit's center (`CameraController::center`)
+/// is small enough to stay precise in `f32`, so only `zoom`/`aspect_ratio`, the
+/// valid lengths of the perturbation reference orbits and the `f32` offset between
+/// the two orbit centers (`orbit2_offset`, below) need to cross to the GPU here;
+/// the high-precision centers themselves never leave the CPU.
+///
+/// `orbit2`/`orbit2_offset` are a second, nearby reference orbit that
+/// `buffer.wgsl` falls back to when Pauldelbrot's glitch criterion fires on
+/// `orbit` (the pixel's perturbation from the primary orbit has diverged far
+/// enough from the true orbit that its escape value can no longer be
+/// trusted). `orbit2_offset` is `orbit2`'s center minus `orbit`'s, so the
+/// shader can translate a pixel's `delta_c` into the secondary orbit's own
+/// delta space without needing the high-precision centers themselves.
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
-    offset: [f32; 2],
     zoom: f32,
     aspect_ratio: f32,
+    orbit_len: i32,
+    orbit2_len: i32,
+    orbit2_offset: [f32; 2],
+    _padding: [f32; 2],
 }
 
 impl CameraUniform {
-    pub fn new(aspect_ratio: f32) -> Self {
+    pub fn new(aspect_ratio: f32, orbit_len: i32) -> Self {
         Self {
             zoom: 1.0,
-            offset: [0., 0.],
             aspect_ratio,
+            orbit_len,
+            orbit2_len: 0,
+            orbit2_offset: [0.0, 0.0],
+            _padding: [0.0, 0.0],
+        }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    pub fn set_orbit_len(&mut self, orbit_len: i32) {
+        self.orbit_len = orbit_len;
+    }
+
+    pub fn set_orbit2(&mut self, orbit2_len: i32, orbit2_offset: [f32; 2]) {
+        self.orbit2_len = orbit2_len;
+        self.orbit2_offset = orbit2_offset;
+    }
+
+    /// Returns a copy of this uniform with `aspect_ratio` overridden, for
+    /// rendering the current view at a resolution other than the window's.
+    pub fn with_aspect_ratio(&self, aspect_ratio: f32) -> Self {
+        Self {
+            aspect_ratio,
+            ..*self
         }
     }
 }
@@ -25,6 +70,20 @@ pub struct CameraController {
     zoom_in: bool,
     zoom_out: bool,
     aspect_ratio: Option<f32>,
+    current_aspect_ratio: f32,
+    window_size: (f32, f32),
+    is_dragging: bool,
+    last_cursor_uv: Option<[f32; 2]>,
+    // Accumulated in `uv_scaled` space (aspect already applied to x);
+    // drained into `center` (scaled by `camera.zoom`) in `update_camera`.
+    pending_pan: [f32; 2],
+    // (cursor uv at scroll time, scroll amount), drained in `update_camera`.
+    pending_zoom: Option<([f32; 2], f32)>,
+    // The Mandelbrot center in full `f64` precision — this is what the
+    // perturbation reference orbit is computed around, so it can keep
+    // resolving detail long after `f32` offsets would dissolve into noise.
+    center: (f64, f64),
+    center_dirty: bool,
 }
 
 impl CameraController {
@@ -37,10 +96,30 @@ impl CameraController {
             zoom_in: false,
             zoom_out: false,
             aspect_ratio: None,
+            current_aspect_ratio: 1.0,
+            window_size: (1.0, 1.0),
+            is_dragging: false,
+            last_cursor_uv: None,
+            pending_pan: [0.0, 0.0],
+            pending_zoom: None,
+            center: (0.0, 0.0),
+            center_dirty: false,
         }
     }
+
+    pub fn center(&self) -> (f64, f64) {
+        self.center
+    }
+
+    /// Returns whether `center` changed since the last call, resetting the flag.
+    pub fn take_center_dirty(&mut self) -> bool {
+        std::mem::replace(&mut self.center_dirty, false)
+    }
     pub fn process_events(&mut self, event: &winit::event::WindowEvent) -> bool {
-        use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+        use winit::event::{
+            ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode,
+            WindowEvent,
+        };
         match event {
             WindowEvent::KeyboardInput {
                 input:
@@ -80,12 +159,54 @@ impl CameraController {
                     _ => false,
                 }
             }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.is_dragging = *state == ElementState::Pressed;
+                if !self.is_dragging {
+                    self.last_cursor_uv = None;
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let uv = self.pixel_to_uv(position.x as f32, position.y as f32);
+                if self.is_dragging {
+                    if let Some(prev) = self.last_cursor_uv {
+                        self.pending_pan[0] -= (uv[0] - prev[0]) * self.current_aspect_ratio;
+                        self.pending_pan[1] -= uv[1] - prev[1];
+                    }
+                }
+                self.last_cursor_uv = Some(uv);
+                self.is_dragging
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let scroll = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(pos) => (pos.y / 20.0) as f32,
+                };
+                if let Some(uv) = self.last_cursor_uv {
+                    self.pending_zoom = Some((uv, scroll));
+                }
+                true
+            }
             _ => false,
         }
     }
 
+    fn pixel_to_uv(&self, x: f32, y: f32) -> [f32; 2] {
+        let (width, height) = self.window_size;
+        [-1.0 + 2.0 * x / width, 1.0 - 2.0 * y / height]
+    }
+
     pub fn update_aspect_ratio(&mut self, aspect_ratio: f32) {
         self.aspect_ratio = Some(aspect_ratio);
+        self.current_aspect_ratio = aspect_ratio;
+    }
+
+    pub fn update_window_size(&mut self, width: f32, height: f32) {
+        self.window_size = (width, height);
     }
 
     pub fn update_camera(&mut self, dt: std::time::Duration, camera: &mut CameraUniform) -> bool {
@@ -100,19 +221,23 @@ impl CameraController {
             updated = true;
         }
         if self.is_up_pressed {
-            camera.offset[1] -= amount;
+            self.center.1 -= amount as f64;
+            self.center_dirty = true;
             updated = true;
         }
         if self.is_down_pressed {
-            camera.offset[1] += amount;
+            self.center.1 += amount as f64;
+            self.center_dirty = true;
             updated = true;
         }
         if self.is_left_pressed {
-            camera.offset[0] += amount;
+            self.center.0 += amount as f64;
+            self.center_dirty = true;
             updated = true;
         }
         if self.is_right_pressed {
-            camera.offset[0] -= amount;
+            self.center.0 -= amount as f64;
+            self.center_dirty = true;
             updated = true;
         }
         if self.zoom_out {
@@ -123,6 +248,24 @@ impl CameraController {
             camera.zoom /= zoom_amount;
             updated = true
         }
+        if self.pending_pan != [0.0, 0.0] {
+            self.center.0 += self.pending_pan[0] as f64 * camera.zoom as f64;
+            self.center.1 += self.pending_pan[1] as f64 * camera.zoom as f64;
+            self.pending_pan = [0.0, 0.0];
+            self.center_dirty = true;
+            updated = true;
+        }
+        if let Some((uv, scroll)) = self.pending_zoom.take() {
+            let zoom_old = camera.zoom;
+            // Scrolling up (away from the user) zooms in, i.e. shrinks `zoom`.
+            let zoom_new = zoom_old * 1.1_f32.powf(-scroll);
+            camera.zoom = zoom_new;
+            let dzoom = (zoom_old - zoom_new) as f64;
+            self.center.0 += uv[0] as f64 * camera.aspect_ratio as f64 * dzoom;
+            self.center.1 += uv[1] as f64 * dzoom;
+            self.center_dirty = true;
+            updated = true;
+        }
         updated
     }
 }
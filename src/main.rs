@@ -1,10 +1,17 @@
 mod app;
 mod camera;
+mod perturbation;
+mod texture;
 
 fn main() {
     env_logger::init_from_env(
         env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "info"),
     );
+    if let Some((width, height, out)) = app::parse_render_args() {
+        app::render_headless_block(width, height, &out);
+        return;
+    }
+
     let mut app = app::App::new_block();
     app.run();
 }